@@ -0,0 +1,129 @@
+use {Date, Schedule};
+
+const OPEN_HOUR: u32 = 7;
+const CLOSE_HOUR: u32 = 23;
+const PIXELS_PER_MINUTE: u32 = 1;
+const DAY_WIDTH: u32 = 160;
+
+impl Schedule {
+    /// Renders `days` days starting from `start` as a self-contained HTML
+    /// page: one column per day, one colored block per free `TimeRange`,
+    /// stacked top-to-bottom by time of day. This is a glanceable
+    /// alternative to dumping `find_available_ranges` with `{:?}`.
+    pub fn to_html(&self, start: Date, days: u32) -> String {
+        let mut dates: Vec<(i32, u8, u8)> = Vec::new();
+        let mut date = start;
+
+        for _ in 0..days {
+            dates.push((date.year, date.month, date.day));
+            date = date.next_day();
+        }
+
+        let grid_height = (CLOSE_HOUR - OPEN_HOUR) * 60 * PIXELS_PER_MINUTE;
+
+        let mut columns = String::new();
+
+        for &(year, month, day) in &dates {
+            let mut blocks = String::new();
+
+            for room in &self.rooms {
+                for date in room.available.keys() {
+                    if (date.year, date.month, date.day) != (year, month, day) {
+                        continue;
+                    }
+
+                    for range in &room.available[date] {
+                        let top = (range.start.as_minutes().saturating_sub(OPEN_HOUR * 60)) * PIXELS_PER_MINUTE;
+                        let height = range.length_minutes() * PIXELS_PER_MINUTE;
+
+                        blocks.push_str(&format!(
+                            "<div class=\"block\" style=\"top: {}px; height: {}px; background: {};\" \
+                             title=\"Room {} ({} person), {:02}:{:02}-{:02}:{:02}\"></div>\n",
+                            top,
+                            height,
+                            capacity_color(room.person_capacity),
+                            room.room_number,
+                            room.person_capacity,
+                            range.start.hour,
+                            range.start.minute,
+                            range.end.hour,
+                            range.end.minute
+                        ));
+                    }
+                }
+            }
+
+            columns.push_str(&format!(
+                "<div class=\"day\">\n<h2>{:04}-{:02}-{:02}</h2>\n<div class=\"grid\" style=\"height: {}px;\">\n{}</div>\n</div>\n",
+                year, month, day, grid_height, blocks
+            ));
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>King Study Room availability</title>\n\
+             <style>\n\
+             body {{ font-family: sans-serif; }}\n\
+             .week {{ display: flex; }}\n\
+             .day {{ width: {}px; margin-right: 8px; }}\n\
+             .grid {{ position: relative; border: 1px solid #ccc; }}\n\
+             .block {{ position: absolute; left: 0; right: 0; opacity: 0.8; }}\n\
+             </style>\n</head>\n<body>\n<div class=\"week\">\n{}</div>\n</body>\n</html>\n",
+            DAY_WIDTH, columns
+        )
+    }
+}
+
+fn capacity_color(person_capacity: u8) -> &'static str {
+    match person_capacity {
+        0..=2 => "#a8d5ba",
+        3..=4 => "#8ecae6",
+        5..=6 => "#ffb703",
+        _ => "#fb8500",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use {Date, KingStudyRoom, Schedule, Time, TimeRange};
+
+    use super::capacity_color;
+
+    fn schedule_with_one_room() -> Schedule {
+        let mut available = HashMap::new();
+        available.insert(Date::new(2024, 1, 1), vec![TimeRange::new(Time::new(9, 0), Time::new(9, 30))]);
+        available.insert(Date::new(2024, 1, 8), vec![TimeRange::new(Time::new(10, 0), Time::new(10, 30))]);
+
+        Schedule{rooms: vec![KingStudyRoom{
+            room_number: 217,
+            person_capacity: 4,
+            available: available,
+        }]}
+    }
+
+    #[test]
+    fn test_to_html_only_renders_the_requested_window() {
+        let html = schedule_with_one_room().to_html(Date::new(2024, 1, 1), 2);
+
+        assert!(html.contains("<h2>2024-01-01</h2>"));
+        assert!(html.contains("<h2>2024-01-02</h2>"));
+        assert!(!html.contains("<h2>2024-01-08</h2>"));
+        assert_eq!(html.matches("class=\"block\"").count(), 1);
+    }
+
+    #[test]
+    fn test_to_html_block_title_names_room_capacity_and_times() {
+        let html = schedule_with_one_room().to_html(Date::new(2024, 1, 1), 1);
+
+        assert!(html.contains("title=\"Room 217 (4 person), 09:00-09:30\""));
+    }
+
+    #[test]
+    fn test_capacity_color_buckets_by_person_capacity() {
+        assert_eq!(capacity_color(2), capacity_color(1));
+        assert_ne!(capacity_color(2), capacity_color(3));
+        assert_ne!(capacity_color(4), capacity_color(5));
+        assert_ne!(capacity_color(6), capacity_color(7));
+    }
+}
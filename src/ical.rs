@@ -0,0 +1,132 @@
+use chrono::Utc;
+
+use {Date, KingStudyRoom, Schedule, Time, TimeRange};
+
+impl KingStudyRoom {
+    /// Serializes every discovered `TimeRange` for every `Date` this room has
+    /// availability data for into RFC 5545 VEVENT blocks.
+    ///
+    /// If `min_minutes` is given, ranges shorter than that are skipped, so
+    /// callers can filter out slots too short to actually reserve.
+    pub fn to_ical(&self, min_minutes: Option<u32>) -> String {
+        let mut dates: Vec<&Date> = self.available.keys().collect();
+        dates.sort_by_key(|d| (d.year, d.month, d.day));
+
+        let mut events = String::new();
+
+        for date in dates {
+            for range in &self.available[date] {
+                if let Some(min) = min_minutes {
+                    if range.length_minutes() < min {
+                        continue;
+                    }
+                }
+
+                events.push_str(&self.to_vevent(date, range));
+            }
+        }
+
+        events
+    }
+
+    fn to_vevent(&self, date: &Date, range: &TimeRange) -> String {
+        let uid = format!(
+            "{:04}{:02}{:02}T{:02}{:02}-room{}@miami-room-availability",
+            date.year, date.month, date.day, range.start.hour, range.start.minute, self.room_number
+        );
+
+        format!(
+            "BEGIN:VEVENT\r\n\
+             UID:{}\r\n\
+             DTSTAMP:{}\r\n\
+             DTSTART:{}\r\n\
+             DTEND:{}\r\n\
+             SUMMARY:King Study Room {} ({} person) available\r\n\
+             END:VEVENT\r\n",
+            uid,
+            Utc::now().format("%Y%m%dT%H%M%SZ"),
+            stamp(date, &range.start),
+            stamp(date, &range.end),
+            self.room_number,
+            self.person_capacity
+        )
+    }
+}
+
+impl Schedule {
+    /// Renders every room's availability as a single VCALENDAR document so
+    /// the whole schedule can be subscribed to from Google Calendar or
+    /// Outlook.
+    ///
+    /// If `min_minutes` is given, ranges shorter than that are omitted.
+    pub fn to_ical(&self, min_minutes: Option<u32>) -> String {
+        let mut body = String::new();
+
+        for room in &self.rooms {
+            body.push_str(&room.to_ical(min_minutes));
+        }
+
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//miami-room-availability//EN\r\n{}END:VCALENDAR\r\n",
+            body
+        )
+    }
+}
+
+fn stamp(date: &Date, time: &Time) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}00",
+        date.year, date.month, date.day, time.hour, time.minute
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use {Date, KingStudyRoom, Schedule, Time, TimeRange};
+
+    fn room() -> KingStudyRoom {
+        let mut available = HashMap::new();
+        available.insert(Date::new(2024, 1, 1), vec![
+            TimeRange::new(Time::new(9, 0), Time::new(9, 30)),
+            TimeRange::new(Time::new(14, 0), Time::new(16, 0)),
+        ]);
+
+        KingStudyRoom{
+            room_number: 217,
+            person_capacity: 4,
+            available: available,
+        }
+    }
+
+    #[test]
+    fn test_to_ical_emits_a_vevent_with_a_stable_uid_and_dtstamp() {
+        let ical = room().to_ical(None);
+
+        assert!(ical.contains("BEGIN:VEVENT"));
+        assert!(ical.contains("UID:20240101T0900-room217@miami-room-availability"));
+        assert!(ical.contains("DTSTAMP:"));
+        assert!(ical.contains("DTSTART:20240101T090000"));
+        assert!(ical.contains("DTEND:20240101T093000"));
+        assert!(ical.contains("SUMMARY:King Study Room 217 (4 person) available"));
+    }
+
+    #[test]
+    fn test_to_ical_filters_ranges_shorter_than_min_minutes() {
+        let ical = room().to_ical(Some(60));
+
+        assert!(!ical.contains("DTSTART:20240101T0900"));
+        assert!(ical.contains("DTSTART:20240101T140000"));
+    }
+
+    #[test]
+    fn test_schedule_to_ical_wraps_every_room_in_one_vcalendar() {
+        let schedule = Schedule{rooms: vec![room()]};
+        let ical = schedule.to_ical(None);
+
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0"));
+        assert!(ical.trim_end().ends_with("END:VCALENDAR"));
+        assert_eq!(ical.matches("BEGIN:VEVENT").count(), 2);
+    }
+}
@@ -3,12 +3,20 @@ extern crate lazy_static;
 extern crate select;
 extern crate regex;
 extern crate core;
+extern crate chrono;
+
+mod ical;
+mod html;
+pub mod solver;
+pub mod recurrence;
 
 use select::document::Document;
 use select::predicate::{And, Class, Name};
 use std::convert::From;
 use std::collections::HashMap;
+use std::str::FromStr;
 use core::cmp::Ordering;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
 
 lazy_static! {
     static ref ROOM_NAME_REGEX: regex::Regex = regex::Regex::new(r"King Study Room (\d+) - (\d+) Person").unwrap();
@@ -20,20 +28,23 @@ pub struct Time {
     minute: u8,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Date {
     year: i32,
     month: u8,
     day: u8,
 }
 
+#[derive(Debug)]
 pub enum ParseError {
     NameDoesNotMatch,
     NoNumber,
     NoCapacity,
+    InvalidDate,
+    InvalidTime,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct KingStudyRoom {
     room_number: u16,
     person_capacity: u8,
@@ -75,6 +86,24 @@ impl Schedule {
         Schedule{rooms: rooms}
     }
 
+    pub fn rooms(&self) -> &[KingStudyRoom] {
+        &self.rooms
+    }
+
+    /// Returns a copy of this schedule with rooms below `min_capacity`
+    /// dropped entirely, and every remaining room's available ranges
+    /// shorter than `min_minutes` removed. Each stored range is tested on
+    /// its own: adjacent ranges are not merged first, as they are in
+    /// `find_available_ranges`.
+    pub fn filtered(&self, min_capacity: u8, min_minutes: u32) -> Schedule {
+        let rooms = self.rooms.iter()
+            .filter(|room| room.person_capacity >= min_capacity)
+            .map(|room| room.filtered(min_minutes))
+            .collect();
+
+        Schedule{rooms: rooms}
+    }
+
     pub fn all_available_at_datetime(&self, d: &Date, t: &Time) -> Vec<&KingStudyRoom> {
         let mut available = Vec::new();
 
@@ -87,17 +116,32 @@ impl Schedule {
         available
     }
 
-    pub fn find_available_ranges(&self, minutes: u8) -> HashMap<u16, Vec<TimeRange>> {
+    /// Finds bookable windows of `minutes` each across every room, keyed by
+    /// room number and the date the window falls on. Returns
+    /// `RangeError::MinutesExceedsMax` if `minutes` is over 120.
+    pub fn find_available_ranges(&self, minutes: u32) -> Result<HashMap<(u16, Date), Vec<TimeRange>>, RangeError> {
         let mut map = HashMap::new();
 
         for room in &self.rooms {
-            map.insert(room.room_number, room.find_available_ranges(minutes));
+            let by_date = match room.find_available_ranges(minutes) {
+                Ok(x) => x,
+                Err(e) => return Err(e),
+            };
+
+            for (date, windows) in by_date {
+                map.insert((room.room_number, date), windows);
+            }
         }
 
-        map
+        Ok(map)
     }
 }
 
+#[derive(Debug)]
+pub enum RangeError {
+    MinutesExceedsMax,
+}
+
 impl KingStudyRoom {
     pub fn from_str(s: &str) -> Result<KingStudyRoom, ParseError> {
         let captures = match ROOM_NAME_REGEX.captures(s) {
@@ -126,28 +170,33 @@ impl KingStudyRoom {
         let (day, start) = match n.attr("ref") {
             Some(x) => {
                 match TimeRange::parse_stamp(x) {
-                    Some(daystart) => daystart,
-                    None => return,
+                    Ok(daystart) => daystart,
+                    Err(_) => return,
                 }
             },
             None => return,
         };
 
-        let end = start.add(&Time::new(0, 30));
-        let range = TimeRange::new(start, end);
+        let (end, overflow) = start.add_with_overflow(&Time::new(0, 30));
 
-        if self.available.contains_key(&day) {
-            let mut intervals = self.available.get_mut(&day).unwrap();
-            intervals.push(range);
-            intervals.sort();
+        if overflow > 0 {
+            // The slot crosses midnight: split it into the tail end of
+            // `day` and the head of the following day, rather than
+            // relabeling the whole (now nonsensical) interval under one
+            // date.
+            self.insert_interval(day, TimeRange::new(start, Time::new(23, 59)));
+            self.insert_interval(day.next_day(), TimeRange::new(Time::new(0, 0), end));
         } else {
-            let mut intervals = Vec::new();
-            intervals.push(range);
-            intervals.sort();
-            self.available.insert(day, intervals);
+            self.insert_interval(day, TimeRange::new(start, end));
         }
     }
 
+    fn insert_interval(&mut self, day: Date, range: TimeRange) {
+        let intervals = self.available.entry(day).or_insert_with(Vec::new);
+        intervals.push(range);
+        intervals.sort();
+    }
+
     pub fn is_available(&self, d: &Date, t: &Time) -> bool {
         match self.available.get(d) {
             Some(intervals) => for interval in intervals {
@@ -160,50 +209,126 @@ impl KingStudyRoom {
         false
     }
 
-    fn find_available_ranges(&self, minutes: u8) -> Vec<TimeRange> {
+    /// Returns true if some single free `TimeRange` on `d` fully covers
+    /// `range`, rather than merely overlapping part of it.
+    pub fn is_available_range(&self, d: &Date, range: &TimeRange) -> bool {
+        match self.available.get(d) {
+            Some(intervals) => intervals.iter().any(|interval| {
+                interval.start <= range.start && range.end <= interval.end
+            }),
+            None => false,
+        }
+    }
+
+    pub fn room_number(&self) -> u16 {
+        self.room_number
+    }
+
+    pub fn person_capacity(&self) -> u8 {
+        self.person_capacity
+    }
+
+    /// Returns a copy of this room with every available range shorter than
+    /// `min_minutes` removed.
+    fn filtered(&self, min_minutes: u32) -> KingStudyRoom {
+        let available = self.available.iter()
+            .map(|(date, ranges)| {
+                let ranges = ranges.iter()
+                    .filter(|range| range.length_minutes() >= min_minutes)
+                    .cloned()
+                    .collect();
+
+                (*date, ranges)
+            })
+            .collect();
+
+        KingStudyRoom{
+            room_number: self.room_number,
+            person_capacity: self.person_capacity,
+            available: available,
+        }
+    }
+
+    /// For each date this room has availability data for: sorts and merges
+    /// overlapping/adjacent free `TimeRange`s into maximal blocks, then
+    /// slices each block into back-to-back bookable windows of `minutes`.
+    fn find_available_ranges(&self, minutes: u32) -> Result<HashMap<Date, Vec<TimeRange>>, RangeError> {
         if minutes > 120 {
-            panic!("You can only reserve up to 2 hours at a time!");
+            return Err(RangeError::MinutesExceedsMax);
         }
-        let minutes = minutes as u32;
-        let mut length_so_far = 0u32;
-        let mut range = TimeRange::new(Time::new(0, 0), Time::new(0, 0));
-        let mut available = Vec::new();
-        let mut last;
-
-        for intervals in self.available.values() {
-            length_so_far = 0;
-            range = TimeRange::new(Time::new(0, 0), Time::new(0, 0));
-            last = TimeRange::new(Time::new(0, 0), Time::new(0, 0));
-
-            for x in intervals {
-                let interval = x.clone();
-                if length_so_far == 0 {
-                    range.start = interval.start;
-                    range.end = interval.end;
-                    length_so_far = interval.length_minutes();
-                } else if interval.start == last.end {
-                    range.end = interval.end;
-                    length_so_far += interval.length_minutes();
-                } else {
-                    if length_so_far >= minutes {
-                        available.push(range);
-                    }
-
-                    length_so_far = 0;
-                    range = TimeRange::new(Time::new(0, 0), Time::new(0, 0));
-                    last = TimeRange::new(Time::new(0, 0), Time::new(0, 0));
-                }
 
-                last = interval;
+        let mut by_date = HashMap::new();
+
+        for (date, intervals) in &self.available {
+            let mut sorted = intervals.clone();
+            sorted.sort();
+
+            let windows: Vec<TimeRange> = merge_intervals(&sorted).iter()
+                .flat_map(|block| bookable_windows(block, minutes))
+                .collect();
+
+            if !windows.is_empty() {
+                by_date.insert(*date, windows);
             }
+        }
 
-            if length_so_far != 0 {
-                available.push(range);
+        Ok(by_date)
+    }
+}
+
+/// Coalesces sorted, possibly-overlapping intervals into maximal
+/// non-overlapping blocks. Adjacent or overlapping intervals
+/// (`interval.start <= last.end`) are merged into a single block.
+fn merge_intervals(sorted: &[TimeRange]) -> Vec<TimeRange> {
+    let mut merged: Vec<TimeRange> = Vec::new();
+
+    for interval in sorted {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => {
+                if interval.end > last.end {
+                    last.end = interval.end;
+                }
             }
+            _ => merged.push(*interval),
         }
+    }
 
-        available
+    merged
+}
+
+/// Slices a merged free block into consecutive, non-overlapping `minutes`
+/// long windows, dropping any remainder too short to book.
+fn bookable_windows(block: &TimeRange, minutes: u32) -> Vec<TimeRange> {
+    if minutes == 0 {
+        return vec![*block];
     }
+
+    let duration = Time::new((minutes / 60) as u8, (minutes % 60) as u8);
+    let mut windows = Vec::new();
+    let mut start = block.start;
+
+    loop {
+        let (end, overflow) = start.add_with_overflow(&duration);
+        if overflow > 0 || end.as_minutes() > block.end.as_minutes() {
+            break;
+        }
+
+        windows.push(TimeRange::new(start, end));
+        start = end;
+    }
+
+    windows
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
 }
 
 impl Date {
@@ -218,6 +343,99 @@ impl Date {
 
         Date{year: year, month: month, day: day}
     }
+
+    /// Today's date in UTC, used to default the `--format html` export
+    /// window to "starting today" when the caller doesn't pick a date.
+    pub fn today() -> Date {
+        let now = Utc::now().naive_utc().date();
+
+        Date{
+            year: now.year(),
+            month: now.month() as u8,
+            day: now.day() as u8,
+        }
+    }
+
+    /// Returns the day of the week, computed with Sakamoto's algorithm.
+    /// Returns the day of the week, computed with Sakamoto's algorithm.
+    ///
+    /// Deliberately plain arithmetic rather than `chrono::NaiveDate`: the
+    /// latter's `from_ymd`/`succ` panic on a calendar date that doesn't
+    /// actually exist (e.g. February 30th), and `Date::new` only checks
+    /// month/day against fixed bounds, not true days-in-month.
+    pub fn weekday(&self) -> Weekday {
+        let t = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let mut y = self.year as i64;
+        if self.month < 3 {
+            y -= 1;
+        }
+
+        let index = (y + y / 4 - y / 100 + y / 400 + t[(self.month - 1) as usize] + self.day as i64) % 7;
+
+        match index {
+            0 => Weekday::Sunday,
+            1 => Weekday::Monday,
+            2 => Weekday::Tuesday,
+            3 => Weekday::Wednesday,
+            4 => Weekday::Thursday,
+            5 => Weekday::Friday,
+            _ => Weekday::Saturday,
+        }
+    }
+
+    /// Returns the calendar date immediately following this one.
+    pub fn next_day(&self) -> Date {
+        let days_in_month = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 => if is_leap_year(self.year) { 29 } else { 28 },
+            _ => unreachable!(),
+        };
+
+        if self.day < days_in_month {
+            Date::new(self.year, self.month, self.day + 1)
+        } else if self.month < 12 {
+            Date::new(self.year, self.month + 1, 1)
+        } else {
+            Date::new(self.year + 1, 1, 1)
+        }
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+impl FromStr for Date {
+    type Err = ParseError;
+
+    /// Parses a date in `YYYY-MM-DD` form, via `chrono::NaiveDate` so
+    /// out-of-range months/days (e.g. `2021-02-30`) are rejected rather
+    /// than accepted and silently truncated later.
+    fn from_str(s: &str) -> Result<Date, ParseError> {
+        let parsed = match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(x) => x,
+            Err(_) => return Err(ParseError::InvalidDate),
+        };
+
+        Ok(Date{
+            year: parsed.year(),
+            month: parsed.month() as u8,
+            day: parsed.day() as u8,
+        })
+    }
 }
 
 impl Time {
@@ -233,23 +451,48 @@ impl Time {
         Time{hour: hour, minute: minute}
     }
 
+    /// Adds `other` as a duration, wrapping past midnight rather than
+    /// overflowing `hour`. Use `add_with_overflow` if you need to know
+    /// whether the result rolled onto the next day.
     pub fn add(&self, other: &Time) -> Time {
-        let mut t = Time{
-            hour: self.hour,
-            minute: self.minute,
-        };
+        self.add_with_overflow(other).0
+    }
 
-        t.minute += other.minute;
-        t.hour += t.minute / 60;
-        t.minute = t.minute % 60;
-        t.hour += other.hour;
+    /// Adds `other` as a duration, returning the wrapped result along with
+    /// the number of days that were rolled over past midnight.
+    pub fn add_with_overflow(&self, other: &Time) -> (Time, u32) {
+        let start = NaiveTime::from_hms(self.hour as u32, 0, 0) + chrono::Duration::minutes(self.minute as i64);
+        let end = start + chrono::Duration::minutes(other.as_minutes() as i64);
+        let overflow = (self.as_minutes() + other.as_minutes()) / (24 * 60);
 
-        t
+        (Time{hour: end.hour() as u8, minute: end.minute() as u8}, overflow)
     }
 
     pub fn as_minutes(&self) -> u32 {
         (self.hour as u32) * 60 + (self.minute as u32)
     }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+}
+
+impl FromStr for Time {
+    type Err = ParseError;
+
+    /// Parses a time in `HH:MM` form, via `chrono::NaiveTime`.
+    fn from_str(s: &str) -> Result<Time, ParseError> {
+        let parsed = match NaiveTime::parse_from_str(s, "%H:%M") {
+            Ok(x) => x,
+            Err(_) => return Err(ParseError::InvalidTime),
+        };
+
+        Ok(Time{hour: parsed.hour() as u8, minute: parsed.minute() as u8})
+    }
 }
 
 impl PartialOrd for Time {
@@ -275,39 +518,40 @@ impl TimeRange {
         TimeRange{start: start, end: end}
     }
 
-    pub fn parse_stamp(stamp: &str) -> Option<(Date, Time)> {
-        let (year_s, tail) = stamp.split_at(4);
-        let (month_s, tail) = tail.split_at(2);
-        let (day_s, tail) = tail.split_at(2);
-        let (hour_s, tail) = tail.split_at(2);
-        let (minute_s, _) = tail.split_at(2);
+    pub fn start(&self) -> Time {
+        self.start
+    }
 
-        let year = match year_s.parse() {
-            Ok(s) => s,
-            Err(_) => return None,
-        };
+    pub fn end(&self) -> Time {
+        self.end
+    }
 
-        let month = match month_s.parse() {
-            Ok(s) => s,
-            Err(_) => return None,
-        };
+    /// Parses the leading `YYYYMMDDHHMM` of `stamp` (as found in the
+    /// scraped HTML's `ref` attributes) via `chrono::NaiveDateTime`, rather
+    /// than trusting fixed-width byte splits on whatever text happened to
+    /// be there. Any trailing characters are ignored.
+    pub fn parse_stamp(stamp: &str) -> Result<(Date, Time), ParseError> {
+        if stamp.len() < 12 {
+            return Err(ParseError::InvalidDate);
+        }
 
-        let day = match day_s.parse() {
-            Ok(s) => s,
-            Err(_) => return None,
+        let parsed = match NaiveDateTime::parse_from_str(&stamp[..12], "%Y%m%d%H%M") {
+            Ok(x) => x,
+            Err(_) => return Err(ParseError::InvalidDate),
         };
 
-        let hour = match hour_s.parse() {
-            Ok(s) => s,
-            Err(_) => return None,
+        let date = Date{
+            year: parsed.year(),
+            month: parsed.month() as u8,
+            day: parsed.day() as u8,
         };
 
-        let minute = match minute_s.parse() {
-            Ok(s) => s,
-            Err(_) => return None,
+        let time = Time{
+            hour: parsed.hour() as u8,
+            minute: parsed.minute() as u8,
         };
 
-        Some((Date::new(year, month, day), Time::new(hour, minute)))
+        Ok((date, time))
     }
 
     pub fn contains_time(&self, time: &Time) -> bool {
@@ -334,15 +578,9 @@ impl PartialOrd for TimeRange {
 
 #[cfg(test)]
 mod tests {
-    use chrono::datetime::DateTime;
-    use super::{ Time };
+    use std::collections::HashMap;
 
-    //#[test]
-    //fn test_date_stamp_parsing() {
-        //let expected = DateTime::parse_from_rfc3339("2016-05-08T08:30:00-00:00").ok().unwrap();
-        //let actual = TimePeriod::parse_stamp("201605080830005").unwrap();
-        //assert_eq!(expected, actual);
-    //}
+    use super::{ bookable_windows, merge_intervals, Date, FromStr, KingStudyRoom, Schedule, Time, TimeRange };
 
     #[test]
     fn test_time_adding() {
@@ -354,4 +592,118 @@ mod tests {
         let actual = Time{hour: 10, minute: 30}.add(&Time{hour: 2, minute: 50});
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_time_adding_rolls_past_midnight() {
+        let (time, overflow) = Time{hour: 23, minute: 45}.add_with_overflow(&Time{hour: 0, minute: 30});
+
+        assert_eq!(time, Time{hour: 0, minute: 15});
+        assert_eq!(overflow, 1);
+    }
+
+    #[test]
+    fn test_date_stamp_parsing() {
+        let (date, time) = TimeRange::parse_stamp("201605080830005").unwrap();
+
+        assert_eq!(date, Date::new(2016, 5, 8));
+        assert_eq!(time, Time::new(8, 30));
+    }
+
+    #[test]
+    fn test_date_from_str() {
+        assert_eq!(Date::from_str("2016-05-08").unwrap(), Date::new(2016, 5, 8));
+        assert!(Date::from_str("2016-02-30").is_err());
+    }
+
+    #[test]
+    fn test_time_from_str() {
+        assert_eq!(Time::from_str("08:30").unwrap(), Time::new(8, 30));
+        assert!(Time::from_str("25:00").is_err());
+    }
+
+    #[test]
+    fn test_merge_intervals_joins_adjacent_and_overlapping() {
+        let intervals = vec![
+            TimeRange::new(Time::new(9, 0), Time::new(9, 30)),
+            TimeRange::new(Time::new(9, 30), Time::new(10, 0)),
+            TimeRange::new(Time::new(10, 15), Time::new(11, 0)),
+            TimeRange::new(Time::new(10, 45), Time::new(12, 0)),
+        ];
+
+        let merged = merge_intervals(&intervals);
+
+        assert_eq!(merged, vec![
+            TimeRange::new(Time::new(9, 0), Time::new(10, 0)),
+            TimeRange::new(Time::new(10, 15), Time::new(12, 0)),
+        ]);
+    }
+
+    #[test]
+    fn test_bookable_windows_slices_block_and_drops_short_remainder() {
+        let block = TimeRange::new(Time::new(9, 0), Time::new(10, 40));
+
+        let windows = bookable_windows(&block, 30);
+
+        assert_eq!(windows, vec![
+            TimeRange::new(Time::new(9, 0), Time::new(9, 30)),
+            TimeRange::new(Time::new(9, 30), Time::new(10, 0)),
+            TimeRange::new(Time::new(10, 0), Time::new(10, 30)),
+        ]);
+    }
+
+    #[test]
+    fn test_bookable_windows_stops_before_rolling_past_midnight() {
+        let block = TimeRange::new(Time::new(23, 0), Time::new(23, 45));
+
+        let windows = bookable_windows(&block, 30);
+
+        assert_eq!(windows, vec![TimeRange::new(Time::new(23, 0), Time::new(23, 30))]);
+    }
+
+    #[test]
+    fn test_room_find_available_ranges_merges_adjacent_slots_before_slicing() {
+        let mut available = HashMap::new();
+        available.insert(Date::new(2024, 1, 1), vec![
+            TimeRange::new(Time::new(9, 0), Time::new(9, 30)),
+            TimeRange::new(Time::new(9, 30), Time::new(10, 0)),
+            TimeRange::new(Time::new(10, 0), Time::new(10, 30)),
+        ]);
+
+        let room = KingStudyRoom{
+            room_number: 100,
+            person_capacity: 4,
+            available: available,
+        };
+
+        let windows = room.find_available_ranges(60).unwrap();
+
+        assert_eq!(windows[&Date::new(2024, 1, 1)], vec![
+            TimeRange::new(Time::new(9, 0), Time::new(10, 0)),
+        ]);
+    }
+
+    #[test]
+    fn test_schedule_filtered_drops_low_capacity_rooms_and_short_ranges() {
+        let mut small_room_available = HashMap::new();
+        small_room_available.insert(Date::new(2024, 1, 1), vec![TimeRange::new(Time::new(9, 0), Time::new(9, 30))]);
+
+        let mut big_room_available = HashMap::new();
+        big_room_available.insert(Date::new(2024, 1, 1), vec![
+            TimeRange::new(Time::new(9, 0), Time::new(9, 30)),
+            TimeRange::new(Time::new(14, 0), Time::new(16, 0)),
+        ]);
+
+        let schedule = Schedule{rooms: vec![
+            KingStudyRoom{room_number: 1, person_capacity: 2, available: small_room_available},
+            KingStudyRoom{room_number: 2, person_capacity: 6, available: big_room_available},
+        ]};
+
+        let filtered = schedule.filtered(4, 60);
+
+        assert_eq!(filtered.rooms().len(), 1);
+        assert_eq!(filtered.rooms()[0].room_number(), 2);
+        assert_eq!(filtered.rooms()[0].available[&Date::new(2024, 1, 1)], vec![
+            TimeRange::new(Time::new(14, 0), Time::new(16, 0)),
+        ]);
+    }
 }
@@ -1,13 +1,158 @@
 extern crate select;
 extern crate miami_room;
+extern crate clap;
+extern crate reqwest;
 
+use std::fs;
+use std::str::FromStr;
+
+use clap::{App, Arg};
 use select::document::Document;
-use std::convert::From;
 use miami_room::*;
 
 pub fn main() {
-    let document = Document::from(include_str!("../example.html"));
+    let matches = App::new("miami-room-availability")
+        .about("Looks up King Library study room availability")
+        .arg(Arg::with_name("source")
+            .long("source")
+            .takes_value(true)
+            .help("Path to a local HTML file, or a URL to fetch (defaults to the bundled example)"))
+        .arg(Arg::with_name("date")
+            .long("date")
+            .takes_value(true)
+            .help("Date to check, in YYYY-MM-DD form"))
+        .arg(Arg::with_name("time")
+            .long("time")
+            .takes_value(true)
+            .help("Time to check, in HH:MM form (requires --date)"))
+        .arg(Arg::with_name("min-minutes")
+            .long("min-minutes")
+            .takes_value(true)
+            .default_value("30")
+            .help("Minimum length in minutes a free block must be to be reported"))
+        .arg(Arg::with_name("min-capacity")
+            .long("min-capacity")
+            .takes_value(true)
+            .default_value("0")
+            .help("Minimum person capacity a room must have to be reported"))
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["text", "json", "ical", "html"])
+            .default_value("text")
+            .help("Output format"))
+        .arg(Arg::with_name("html-days")
+            .long("html-days")
+            .takes_value(true)
+            .default_value("7")
+            .help("Number of days the --format html week grid should span, starting at --date (defaults to today)"))
+        .get_matches();
+
+    let document = load_document(matches.value_of("source"));
     let schedule = Schedule::new(&document);
-    //println!("{:?}", schedule.all_available_at_datetime(&Date::new(2016, 05, 08), &Time::new(16, 30)));
-    println!("{:?}", schedule.find_available_ranges(60));
+
+    let min_capacity: u8 = matches.value_of("min-capacity").unwrap().parse()
+        .expect("--min-capacity must be a number");
+    let min_minutes: u32 = matches.value_of("min-minutes").unwrap().parse()
+        .expect("--min-minutes must be a number");
+
+    match matches.value_of("format").unwrap() {
+        "ical" => println!("{}", schedule.filtered(min_capacity, 0).to_ical(Some(min_minutes))),
+        "html" => {
+            let start = match matches.value_of("date") {
+                Some(date) => Date::from_str(date).expect("invalid --date, expected YYYY-MM-DD"),
+                None => Date::today(),
+            };
+            let days: u32 = matches.value_of("html-days").unwrap().parse()
+                .expect("--html-days must be a number");
+
+            println!("{}", schedule.filtered(min_capacity, min_minutes).to_html(start, days));
+        }
+        format => print_query(&matches, &schedule, min_minutes, min_capacity, format),
+    }
+}
+
+fn load_document(source: Option<&str>) -> Document {
+    let html = match source {
+        Some(source) if source.starts_with("http://") || source.starts_with("https://") => {
+            reqwest::get(source)
+                .expect("failed to fetch --source URL")
+                .text()
+                .expect("failed to read response body")
+        }
+        Some(source) => fs::read_to_string(source).expect("failed to read --source file"),
+        None => include_str!("../example.html").to_string(),
+    };
+
+    Document::from(html.as_str())
+}
+
+fn print_query(matches: &clap::ArgMatches, schedule: &Schedule, min_minutes: u32, min_capacity: u8, format: &str) {
+    match (matches.value_of("date"), matches.value_of("time")) {
+        (Some(date), Some(time)) => {
+            let date = Date::from_str(date).expect("invalid --date, expected YYYY-MM-DD");
+            let time = Time::from_str(time).expect("invalid --time, expected HH:MM");
+
+            let rooms: Vec<&KingStudyRoom> = schedule.all_available_at_datetime(&date, &time)
+                .into_iter()
+                .filter(|room| room.person_capacity() >= min_capacity)
+                .collect();
+
+            match format {
+                "json" => println!("{}", rooms_to_json(&rooms)),
+                _ => println!("{:?}", rooms),
+            }
+        }
+        _ => {
+            let ranges = schedule.find_available_ranges(min_minutes)
+                .expect("--min-minutes must be at most 120");
+
+            let capacities: std::collections::HashMap<u16, u8> = schedule.rooms().iter()
+                .map(|room| (room.room_number(), room.person_capacity()))
+                .collect();
+
+            let ranges: std::collections::HashMap<(u16, Date), Vec<TimeRange>> = ranges.into_iter()
+                .filter(|&((room_number, _), _)| capacities[&room_number] >= min_capacity)
+                .collect();
+
+            match format {
+                "json" => println!("{}", ranges_to_json(&ranges)),
+                _ => println!("{:?}", ranges),
+            }
+        }
+    }
+}
+
+fn rooms_to_json(rooms: &[&KingStudyRoom]) -> String {
+    let entries: Vec<String> = rooms.iter()
+        .map(|room| format!(
+            "{{\"room_number\":{},\"person_capacity\":{}}}",
+            room.room_number(), room.person_capacity()
+        ))
+        .collect();
+
+    format!("[{}]", entries.join(","))
+}
+
+fn ranges_to_json(ranges: &std::collections::HashMap<(u16, Date), Vec<TimeRange>>) -> String {
+    let entries: Vec<String> = ranges.iter()
+        .map(|(&(room_number, date), windows)| format!(
+            "\"{}-{:04}-{:02}-{:02}\":{}",
+            room_number, date.year(), date.month(), date.day(), windows_to_json(windows)
+        ))
+        .collect();
+
+    format!("{{{}}}", entries.join(","))
+}
+
+fn windows_to_json(windows: &[TimeRange]) -> String {
+    let entries: Vec<String> = windows.iter()
+        .map(|range| format!(
+            "{{\"start\":\"{:02}:{:02}\",\"end\":\"{:02}:{:02}\"}}",
+            range.start().hour(), range.start().minute(),
+            range.end().hour(), range.end().minute()
+        ))
+        .collect();
+
+    format!("[{}]", entries.join(","))
 }
@@ -0,0 +1,130 @@
+use {Date, Schedule, TimeRange, Weekday};
+
+/// A weekly recurrence pattern, e.g. "every Monday and Wednesday
+/// 14:00-16:00". Expanding it against a `Schedule` walks forward from a
+/// base date and reports, for each matching weekday, which rooms are free
+/// across the whole `range`.
+pub struct Recurrence {
+    pub weekdays: Vec<Weekday>,
+    pub range: TimeRange,
+    pub count: u32,
+}
+
+impl Recurrence {
+    pub fn expand<'a>(self, schedule: &'a Schedule, start: Date) -> RecurrenceIter<'a> {
+        RecurrenceIter {
+            schedule: schedule,
+            recurrence: self,
+            current: start,
+            found: 0,
+        }
+    }
+}
+
+pub struct RecurrenceIter<'a> {
+    schedule: &'a Schedule,
+    recurrence: Recurrence,
+    current: Date,
+    found: u32,
+}
+
+/// How many calendar days `RecurrenceIter` will scan looking for the next
+/// matching weekday before giving up. Guards against a recurrence whose
+/// `weekdays` never matches (e.g. empty) spinning forever.
+const MAX_DAYS_SCANNED: u32 = 366 * 50;
+
+impl<'a> Iterator for RecurrenceIter<'a> {
+    type Item = (Date, Vec<u16>);
+
+    fn next(&mut self) -> Option<(Date, Vec<u16>)> {
+        let mut scanned = 0;
+
+        loop {
+            if self.found >= self.recurrence.count || scanned >= MAX_DAYS_SCANNED {
+                return None;
+            }
+
+            let date = self.current;
+            self.current = self.current.next_day();
+            scanned += 1;
+
+            if !self.recurrence.weekdays.contains(&date.weekday()) {
+                continue;
+            }
+
+            self.found += 1;
+
+            let rooms: Vec<u16> = self.schedule.rooms.iter()
+                .filter(|room| room.is_available_range(&date, &self.recurrence.range))
+                .map(|room| room.room_number)
+                .collect();
+
+            return Some((date, rooms));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Recurrence;
+    use {Date, KingStudyRoom, Schedule, Time, TimeRange, Weekday};
+
+    fn schedule_with_one_room() -> Schedule {
+        let mut available = HashMap::new();
+        available.insert(Date::new(2024, 1, 1), vec![TimeRange::new(Time::new(14, 0), Time::new(16, 0))]);
+
+        Schedule{rooms: vec![KingStudyRoom{
+            room_number: 100,
+            person_capacity: 4,
+            available: available,
+        }]}
+    }
+
+    #[test]
+    fn test_expand_skips_non_matching_weekdays_and_stops_at_count() {
+        let schedule = schedule_with_one_room();
+
+        // 2024-01-01 and 2024-01-08 are both Mondays.
+        let recurrence = Recurrence {
+            weekdays: vec![Weekday::Monday],
+            range: TimeRange::new(Time::new(14, 0), Time::new(16, 0)),
+            count: 2,
+        };
+
+        let dates: Vec<Date> = recurrence.expand(&schedule, Date::new(2024, 1, 1))
+            .map(|(date, _)| date)
+            .collect();
+
+        assert_eq!(dates, vec![Date::new(2024, 1, 1), Date::new(2024, 1, 8)]);
+    }
+
+    #[test]
+    fn test_expand_reports_available_rooms() {
+        let schedule = schedule_with_one_room();
+
+        let recurrence = Recurrence {
+            weekdays: vec![Weekday::Monday],
+            range: TimeRange::new(Time::new(14, 0), Time::new(16, 0)),
+            count: 1,
+        };
+
+        let (_, rooms) = recurrence.expand(&schedule, Date::new(2024, 1, 1)).next().unwrap();
+        assert_eq!(rooms, vec![100]);
+    }
+
+    #[test]
+    fn test_expand_with_no_weekdays_terminates() {
+        let schedule = schedule_with_one_room();
+
+        let recurrence = Recurrence {
+            weekdays: vec![],
+            range: TimeRange::new(Time::new(14, 0), Time::new(16, 0)),
+            count: 5,
+        };
+
+        let results: Vec<_> = recurrence.expand(&schedule, Date::new(2024, 1, 1)).collect();
+        assert!(results.is_empty());
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use {merge_intervals, Date, Schedule, Time, TimeRange};
+
+pub type RequestId = u32;
+
+/// A single study group's reservation need: `duration_minutes` within
+/// `window` on `date`, in a room holding at least `min_capacity` people.
+#[derive(Debug, Clone)]
+pub struct ReservationRequest {
+    pub id: RequestId,
+    pub date: Date,
+    pub window: TimeRange,
+    pub duration_minutes: u32,
+    pub min_capacity: u8,
+}
+
+/// Greedily assigns each request to a concrete room and sub-interval of its
+/// window, using earliest-deadline-first ordering (requests whose window
+/// ends soonest are placed first) and first-fit room selection. No two
+/// assignments on the same room ever overlap.
+///
+/// Returns the successful assignments keyed by request id, plus the ids of
+/// requests that could not be satisfied by any room.
+pub fn assign_rooms(
+    schedule: &Schedule,
+    requests: &[ReservationRequest],
+) -> (HashMap<RequestId, (u16, TimeRange)>, Vec<RequestId>) {
+    let mut sorted: Vec<&ReservationRequest> = requests.iter().collect();
+    sorted.sort_by_key(|r| r.window.end);
+
+    let mut remaining: HashMap<(u16, Date), Vec<TimeRange>> = HashMap::new();
+    let mut assignments = HashMap::new();
+    let mut unsatisfiable = Vec::new();
+
+    for request in sorted {
+        let mut assigned = false;
+
+        for room in &schedule.rooms {
+            if room.person_capacity < request.min_capacity {
+                continue;
+            }
+
+            let key = (room.room_number, request.date);
+            if !remaining.contains_key(&key) {
+                // The scraper only ever records 30-minute slots, so merge
+                // adjacent/overlapping ones into maximal free blocks before
+                // trying to fit anything longer than that into them.
+                let free = match room.available.get(&request.date) {
+                    Some(intervals) => {
+                        let mut sorted = intervals.clone();
+                        sorted.sort();
+                        merge_intervals(&sorted)
+                    }
+                    None => Vec::new(),
+                };
+                remaining.insert(key, free);
+            }
+
+            let free = remaining.get_mut(&key).unwrap();
+
+            match fit_index(free, &request.window, request.duration_minutes) {
+                Some(index) => {
+                    let interval = free.remove(index);
+                    let start = if interval.start > request.window.start {
+                        interval.start
+                    } else {
+                        request.window.start
+                    };
+                    let duration = Time::new(
+                        (request.duration_minutes / 60) as u8,
+                        (request.duration_minutes % 60) as u8,
+                    );
+                    let end = start.add(&duration);
+
+                    if interval.start < start {
+                        free.push(TimeRange::new(interval.start, start));
+                    }
+                    if end < interval.end {
+                        free.push(TimeRange::new(end, interval.end));
+                    }
+                    free.sort();
+
+                    assignments.insert(request.id, (room.room_number, TimeRange::new(start, end)));
+                    assigned = true;
+                    break;
+                }
+                None => continue,
+            }
+        }
+
+        if !assigned {
+            unsatisfiable.push(request.id);
+        }
+    }
+
+    (assignments, unsatisfiable)
+}
+
+/// Finds the first free interval that overlaps `window` by at least
+/// `duration_minutes`.
+fn fit_index(free: &[TimeRange], window: &TimeRange, duration_minutes: u32) -> Option<usize> {
+    free.iter().position(|interval| {
+        let start = if interval.start > window.start {
+            interval.start
+        } else {
+            window.start
+        };
+        let end = if interval.end < window.end {
+            interval.end
+        } else {
+            window.end
+        };
+
+        end > start && end.as_minutes() - start.as_minutes() >= duration_minutes
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{assign_rooms, ReservationRequest};
+    use {Date, KingStudyRoom, Schedule, Time, TimeRange};
+
+    fn schedule_with_one_room() -> Schedule {
+        let mut available = HashMap::new();
+        available.insert(Date::new(2024, 1, 1), vec![TimeRange::new(Time::new(9, 0), Time::new(11, 0))]);
+
+        Schedule{rooms: vec![KingStudyRoom{
+            room_number: 100,
+            person_capacity: 4,
+            available: available,
+        }]}
+    }
+
+    #[test]
+    fn test_earlier_deadline_wins_the_only_room() {
+        let schedule = schedule_with_one_room();
+
+        let requests = vec![
+            ReservationRequest{
+                id: 1,
+                date: Date::new(2024, 1, 1),
+                window: TimeRange::new(Time::new(9, 0), Time::new(11, 0)),
+                duration_minutes: 120,
+                min_capacity: 0,
+            },
+            ReservationRequest{
+                id: 2,
+                date: Date::new(2024, 1, 1),
+                window: TimeRange::new(Time::new(9, 0), Time::new(10, 0)),
+                duration_minutes: 60,
+                min_capacity: 0,
+            },
+        ];
+
+        let (assignments, unsatisfiable) = assign_rooms(&schedule, &requests);
+
+        assert_eq!(assignments[&2], (100, TimeRange::new(Time::new(9, 0), Time::new(10, 0))));
+        assert_eq!(unsatisfiable, vec![1]);
+    }
+
+    #[test]
+    fn test_no_room_meets_min_capacity() {
+        let schedule = schedule_with_one_room();
+
+        let requests = vec![ReservationRequest{
+            id: 1,
+            date: Date::new(2024, 1, 1),
+            window: TimeRange::new(Time::new(9, 0), Time::new(11, 0)),
+            duration_minutes: 60,
+            min_capacity: 5,
+        }];
+
+        let (assignments, unsatisfiable) = assign_rooms(&schedule, &requests);
+
+        assert!(assignments.is_empty());
+        assert_eq!(unsatisfiable, vec![1]);
+    }
+
+    #[test]
+    fn test_partial_assignment_splits_remaining_interval_in_two() {
+        let mut available = HashMap::new();
+        available.insert(Date::new(2024, 1, 1), vec![TimeRange::new(Time::new(9, 0), Time::new(12, 0))]);
+
+        let schedule = Schedule{rooms: vec![KingStudyRoom{
+            room_number: 100,
+            person_capacity: 4,
+            available: available,
+        }]};
+
+        // `middle` is processed first (earliest deadline) and sits strictly
+        // inside the room's one free interval, leaving a piece on either
+        // side. `before` and `after` then each claim one of those pieces.
+        let requests = vec![
+            ReservationRequest{
+                id: 1, // middle
+                date: Date::new(2024, 1, 1),
+                window: TimeRange::new(Time::new(9, 15), Time::new(9, 45)),
+                duration_minutes: 30,
+                min_capacity: 0,
+            },
+            ReservationRequest{
+                id: 2, // before
+                date: Date::new(2024, 1, 1),
+                window: TimeRange::new(Time::new(9, 0), Time::new(11, 0)),
+                duration_minutes: 15,
+                min_capacity: 0,
+            },
+            ReservationRequest{
+                id: 3, // after
+                date: Date::new(2024, 1, 1),
+                window: TimeRange::new(Time::new(9, 0), Time::new(12, 0)),
+                duration_minutes: 120,
+                min_capacity: 0,
+            },
+        ];
+
+        let (assignments, unsatisfiable) = assign_rooms(&schedule, &requests);
+
+        assert!(unsatisfiable.is_empty());
+        assert_eq!(assignments[&1], (100, TimeRange::new(Time::new(9, 15), Time::new(9, 45))));
+        assert_eq!(assignments[&2], (100, TimeRange::new(Time::new(9, 0), Time::new(9, 15))));
+        assert_eq!(assignments[&3], (100, TimeRange::new(Time::new(9, 45), Time::new(11, 45))));
+    }
+}